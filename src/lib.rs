@@ -14,69 +14,389 @@
 
 use glob::glob;
 use regex::{escape, Regex};
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::str::FromStr;
-use swc_core::common::DUMMY_SP;
+use std::sync::Arc;
+use swc_core::common::{FileName, Mark, SyntaxContext, DUMMY_SP};
 use swc_core::ecma::ast::{
-    BindingIdent, Decl, Expr, Ident, ImportDecl, ImportDefaultSpecifier, ImportSpecifier,
-    KeyValueProp, Module, ModuleDecl, ModuleItem, ObjectLit, Pat, Program, Prop, PropName,
-    PropOrSpread, Stmt, Str, VarDecl, VarDeclKind, VarDeclarator,
+    ArrowExpr, BindingIdent, BlockStmtOrExpr, CallExpr, Callee, Decl, Expr, ExprOrSpread, ExprStmt,
+    Ident, Import, ImportDecl, ImportDefaultSpecifier, ImportSpecifier, KeyValueProp, Lit, Module,
+    ModuleDecl, ModuleItem, ObjectLit, Pat, Program, Prop, PropName, PropOrSpread, Stmt, Str,
+    VarDecl, VarDeclKind, VarDeclarator,
 };
 use swc_core::ecma::visit::{Fold, FoldWith};
 use swc_core::plugin::{
     metadata::TransformPluginMetadataContextKind, plugin_transform,
     proxies::TransformPluginProgramMetadata,
 };
+use swc_ecma_loader::resolve::Resolve;
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Config {
+    #[serde(default)]
+    mode: ImportMode,
+    #[serde(default)]
+    exclude: Vec<String>,
+    #[serde(default)]
+    module: ModuleFormat,
+    #[serde(default)]
+    on_duplicate: DuplicateStrategy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum DuplicateStrategy {
+    Error,
+    Rename,
+}
+
+impl Default for DuplicateStrategy {
+    fn default() -> Self {
+        DuplicateStrategy::Error
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ModuleFormat {
+    Esm,
+    Cjs,
+}
+
+impl Default for ModuleFormat {
+    fn default() -> Self {
+        ModuleFormat::Esm
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ImportMode {
+    Eager,
+    Lazy,
+}
+
+impl Default for ImportMode {
+    fn default() -> Self {
+        ImportMode::Eager
+    }
+}
 
 pub struct GlobImporter {
     cwd: PathBuf,
     file_name: PathBuf,
     id_counter: usize,
+    mode: ImportMode,
+    unresolved_mark: Mark,
+    exclude: Vec<String>,
+    module: ModuleFormat,
+    resolver: Option<Arc<dyn Resolve>>,
+    on_duplicate: DuplicateStrategy,
+}
+
+struct ParsedSource {
+    include: String,
+    excludes: Vec<String>,
 }
 
 #[derive(Debug)]
 struct WildcardImport {
     ident_import: Ident,
-    ident_obj: String,
+    key_path: Vec<String>,
     import_src: String,
 }
 
+enum MapNode {
+    Leaf(Expr),
+    Branch(Vec<(String, MapNode)>),
+}
+
 impl GlobImporter {
-    fn is_valid_wildcard_import(decl: &ImportDecl) -> bool {
-        decl.src.value.matches('*').count() == 1
+    fn parse_source(&self, src: &str) -> Option<ParsedSource> {
+        let mut include = None;
+        let mut excludes = self.exclude.clone();
+
+        for token in src.split(|c: char| c == ',' || c.is_whitespace()) {
+            if token.is_empty() {
+                continue;
+            }
+            if let Some(pattern) = token.strip_prefix('!') {
+                excludes.push(pattern.to_string());
+            } else {
+                include = Some(token.to_string());
+            }
+        }
+
+        Some(ParsedSource {
+            include: include?,
+            excludes,
+        })
+    }
+
+    fn is_valid_wildcard_import(&self, decl: &ImportDecl) -> bool {
+        let has_supported_specifiers = matches!(
+            decl.specifiers.as_slice(),
+            [] | [ImportSpecifier::Default(_)] | [ImportSpecifier::Namespace(_)]
+        );
+
+        has_supported_specifiers
+            && self
+                .parse_source(&decl.src.value)
+                .is_some_and(|parsed| parsed.include.contains('*'))
+    }
+
+    fn wildcard_capture_regex(include: &str) -> (Regex, Vec<bool>) {
+        let mut pattern = String::from("^");
+        let mut is_globstar = vec![];
+        let mut chars = include.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c == '*' {
+                if chars.peek() == Some(&'*') {
+                    chars.next();
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        // ** can match zero directories, which collapses this / too
+                        pattern.push_str("(?:(.*)/)?");
+                    } else {
+                        pattern.push_str("(.*)");
+                    }
+                    is_globstar.push(true);
+                } else {
+                    pattern.push_str("([^/]*)");
+                    is_globstar.push(false);
+                }
+            } else {
+                pattern.push_str(&escape(&c.to_string()));
+            }
+        }
+        pattern.push('$');
+
+        (Regex::new(&pattern).unwrap(), is_globstar)
+    }
+
+    fn capture_to_key(capture: &str, is_globstar: bool) -> String {
+        if is_globstar {
+            capture
+                .split('/')
+                .filter(|segment| !segment.is_empty())
+                .map(Self::create_valid_property_name)
+                .collect::<Vec<_>>()
+                .join("_")
+        } else {
+            Self::create_valid_property_name(capture)
+        }
     }
 
     fn expand_wildcard(&mut self, decl: &ImportDecl) -> Vec<WildcardImport> {
+        let parsed = self
+            .parse_source(&decl.src.value)
+            .expect("is_valid_wildcard_import should have filtered out non-wildcard imports");
+        let exclude_matchers: Vec<glob::Pattern> = parsed
+            .excludes
+            .iter()
+            .map(|pattern| {
+                // matched against `relative_path` below, which never starts with "./"
+                let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+                glob::Pattern::new(pattern).expect("invalid exclude glob pattern")
+            })
+            .collect();
+
         let pattern = {
             self.cwd
                 .join(self.file_name.clone())
-                .with_file_name(decl.src.value.to_string())
+                .with_file_name(parsed.include.clone())
         };
 
-        let re = Regex::new(&escape(&decl.src.value).replace(r"\*", "(.*)")).unwrap();
-        glob(pattern.to_str().unwrap())
+        let resolver = self.resolver.clone();
+        // `Path` normalizes away a mid-path "./" component (it's only kept when leading), so
+        // `relative_path` below never has one even though `parsed.include` can start with it;
+        // strip it here too so the anchored regex lines up with what `relative_path` actually is.
+        let re_include = parsed.include.strip_prefix("./").unwrap_or(&parsed.include);
+        let (re, is_globstar) = Self::wildcard_capture_regex(re_include);
+        let expanded: Vec<WildcardImport> = glob(pattern.to_str().unwrap())
             .expect("Failed to read glob pattern")
-            .map(|result| match result {
+            .filter_map(|result| match result {
                 Ok(path) => {
-                    let caps = re.captures(path.to_str().unwrap()).unwrap();
-                    let variable_filename_part = caps.get(1).unwrap().as_str();
-
                     let xxx = self.cwd.join(self.file_name.parent().unwrap());
                     let relative_path = path.strip_prefix(&xxx).unwrap().to_str().unwrap();
 
-                    WildcardImport {
-                        ident_import: self.next_variable_id(),
-                        ident_obj: Self::create_valid_property_name(variable_filename_part),
-                        import_src: if relative_path.starts_with('.') {
-                            relative_path.to_string()
-                        } else {
-                            format!("./{relative_path}")
-                        },
+                    // anchored against relative_path, not the absolute path, so a directory
+                    // earlier in cwd/file_name that happens to repeat part of the include
+                    // pattern can't be mistaken for the start of the globstar's capture
+                    let caps = re.captures(relative_path).unwrap();
+                    // an optional globstar group that matched zero dirs doesn't participate at all
+                    let key_path: Vec<String> = is_globstar
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, &globstar)| {
+                            let capture = caps.get(i + 1)?.as_str();
+                            if globstar && capture.is_empty() {
+                                return None;
+                            }
+                            Some(Self::capture_to_key(capture, globstar))
+                        })
+                        .collect();
+
+                    let import_src = if relative_path.starts_with('.') {
+                        relative_path.to_string()
+                    } else {
+                        format!("./{relative_path}")
+                    };
+                    let import_src = match &resolver {
+                        Some(resolver) => self.resolve_import_src(resolver.as_ref(), &import_src),
+                        None => import_src,
+                    };
+
+                    if exclude_matchers
+                        .iter()
+                        .any(|matcher| matcher.matches(relative_path))
+                    {
+                        return None;
                     }
+
+                    Some(WildcardImport {
+                        ident_import: self.next_variable_id(),
+                        key_path,
+                        import_src,
+                    })
                 }
                 Err(e) => panic!("{e:?}"),
             })
-            .collect()
+            .collect();
+
+        if decl.specifiers.is_empty() {
+            return expanded;
+        }
+
+        self.resolve_duplicate_keys(expanded)
+    }
+
+    fn resolve_duplicate_keys(&self, mut expanded: Vec<WildcardImport>) -> Vec<WildcardImport> {
+        let mut groups: HashMap<Vec<String>, Vec<usize>> = HashMap::new();
+        for (idx, import) in expanded.iter().enumerate() {
+            groups.entry(import.key_path.clone()).or_default().push(idx);
+        }
+
+        for (key_path, mut idxs) in groups {
+            if idxs.len() < 2 {
+                continue;
+            }
+            idxs.sort_by(|&a, &b| expanded[a].import_src.cmp(&expanded[b].import_src));
+            self.report_duplicate(&mut expanded, &idxs, &key_path);
+        }
+
+        // a match whose key_path is a strict prefix of another's collides the same way once
+        // `insert_into_map_tree` builds the nested object: `{ sub: v1, sub: { thing: v2 } }`
+        // duplicates the `sub` key even though the two key_paths never compared equal above.
+        let mut order: Vec<usize> = (0..expanded.len()).collect();
+        order.sort_by(|&a, &b| expanded[a].key_path.cmp(&expanded[b].key_path));
+
+        let mut i = 0;
+        while i < order.len() {
+            let prefix = expanded[order[i]].key_path.clone();
+            let mut j = i + 1;
+            while j < order.len()
+                && expanded[order[j]].key_path.len() > prefix.len()
+                && expanded[order[j]].key_path.starts_with(&prefix)
+            {
+                j += 1;
+            }
+
+            if j > i + 1 {
+                // order[i] is the shortest key_path in the block (its own entry), and everything
+                // after it is strictly longer and needs it to stay a Branch, so renaming has to
+                // move the leaf out of the way rather than the deeper entries sharing its prefix.
+                let leaf_idx = order[i];
+                let mut colliding: Vec<_> = order[i..j].to_vec();
+                colliding.sort_by(|&a, &b| expanded[a].import_src.cmp(&expanded[b].import_src));
+                let colliding: Vec<_> = colliding
+                    .into_iter()
+                    .map(|idx| expanded[idx].import_src.clone())
+                    .collect();
+
+                match self.on_duplicate {
+                    DuplicateStrategy::Error => {
+                        panic!(
+                            "glob-import: \"{}\" and \"{}\" both sanitize to the key \"{}\"; \
+                             rename one of them or set `onDuplicate: \"rename\"` in the plugin \
+                             config",
+                            colliding[0],
+                            colliding[1],
+                            prefix.join(".")
+                        );
+                    }
+                    DuplicateStrategy::Rename => {
+                        if let Some(last_key) = expanded[leaf_idx].key_path.last_mut() {
+                            last_key.push_str("_2");
+                        }
+                    }
+                }
+            }
+            i = j;
+        }
+
+        expanded
+    }
+
+    fn report_duplicate(
+        &self,
+        expanded: &mut [WildcardImport],
+        idxs: &[usize],
+        key_path: &[String],
+    ) {
+        match self.on_duplicate {
+            DuplicateStrategy::Error => {
+                let paths: Vec<_> = idxs.iter().map(|&i| expanded[i].import_src.clone()).collect();
+                panic!(
+                    "glob-import: \"{}\" and \"{}\" both sanitize to the key \"{}\"; rename one \
+                     of them or set `onDuplicate: \"rename\"` in the plugin config",
+                    paths[0],
+                    paths[1],
+                    key_path.join(".")
+                );
+            }
+            DuplicateStrategy::Rename => {
+                for (n, &idx) in idxs.iter().enumerate().skip(1) {
+                    if let Some(last_key) = expanded[idx].key_path.last_mut() {
+                        last_key.push_str(&format!("_{}", n + 1));
+                    }
+                }
+            }
+        }
+    }
+
+    fn resolve_import_src(&self, resolver: &dyn Resolve, src: &str) -> String {
+        let base = FileName::Real(self.cwd.join(&self.file_name));
+        match resolver.resolve(&base, src) {
+            Ok(resolution) => match resolution.filename {
+                FileName::Real(path) => path.to_string_lossy().into_owned(),
+                other => other.to_string(),
+            },
+            Err(_) => src.to_string(),
+        }
+    }
+
+    fn make_require_call(&self, import_src: &str) -> CallExpr {
+        CallExpr {
+            span: DUMMY_SP,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident::new(
+                "require".into(),
+                self.marked_span(),
+            )))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    raw: None,
+                    value: import_src.into(),
+                }))),
+            }],
+            type_args: None,
+        }
     }
 
     fn create_valid_property_name(ident: &str) -> String {
@@ -91,30 +411,167 @@ impl GlobImporter {
         .into_owned()
     }
 
+    fn make_lazy_import_value(import_src: &str) -> Expr {
+        Expr::Arrow(ArrowExpr {
+            span: DUMMY_SP,
+            params: vec![],
+            body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                callee: Callee::Import(Import { span: DUMMY_SP }),
+                args: vec![ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Lit(Lit::Str(Str {
+                        span: DUMMY_SP,
+                        raw: None,
+                        value: import_src.into(),
+                    }))),
+                }],
+                type_args: None,
+            })))),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+        })
+    }
+
+    fn insert_into_map_tree(
+        &self,
+        tree: &mut Vec<(String, MapNode)>,
+        mut key_path: Vec<String>,
+        value: Expr,
+    ) {
+        let key = key_path.remove(0);
+
+        if key_path.is_empty() {
+            tree.push((key, MapNode::Leaf(value)));
+            return;
+        }
+
+        match tree.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, MapNode::Branch(children))) => {
+                self.insert_into_map_tree(children, key_path, value);
+            }
+            Some((_, MapNode::Leaf(_))) => {
+                // `resolve_duplicate_keys` already panics (or renames) on any key_path that is a
+                // strict prefix of another, so a leaf landing here with a deeper key_path still
+                // to insert would silently duplicate the `key` entry and clobber the leaf.
+                unreachable!(
+                    "resolve_duplicate_keys should have caught \"{key}\" colliding with a \
+                     deeper key_path before insert_into_map_tree ran"
+                );
+            }
+            None => {
+                let mut children = vec![];
+                self.insert_into_map_tree(&mut children, key_path, value);
+                tree.push((key, MapNode::Branch(children)));
+            }
+        }
+    }
+
+    fn map_tree_to_object_lit(&self, tree: Vec<(String, MapNode)>) -> ObjectLit {
+        ObjectLit {
+            span: DUMMY_SP,
+            props: tree
+                .into_iter()
+                .map(|(key, node)| {
+                    let value = match node {
+                        MapNode::Leaf(expr) => expr,
+                        MapNode::Branch(children) => {
+                            Expr::Object(self.map_tree_to_object_lit(children))
+                        }
+                    };
+                    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                        key: PropName::Ident(Ident {
+                            span: self.marked_span(),
+                            optional: false,
+                            sym: key.into(),
+                        }),
+                        value: Box::new(value),
+                    })))
+                })
+                .collect(),
+        }
+    }
+
     fn split_wildcard_import(&mut self, decl: &ImportDecl) -> Vec<ModuleItem> {
+        let expanded = self.expand_wildcard(decl);
+
+        if decl.specifiers.is_empty() {
+            return expanded
+                .iter()
+                .map(|import| match self.module {
+                    ModuleFormat::Esm => ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                        span: DUMMY_SP,
+                        specifiers: vec![],
+                        src: Box::new(Str {
+                            span: DUMMY_SP,
+                            raw: None,
+                            value: import.import_src.clone().into(),
+                        }),
+                        type_only: false,
+                        asserts: None,
+                    })),
+                    ModuleFormat::Cjs => ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+                        span: DUMMY_SP,
+                        expr: Box::new(Expr::Call(self.make_require_call(&import.import_src))),
+                    })),
+                })
+                .collect();
+        }
+
         let ident = match decl.specifiers.first() {
             Some(ImportSpecifier::Default(x)) => x.local.clone(),
-            Some(_) => panic!("TODO2"),
-            None => panic!("TODO3"),
+            Some(ImportSpecifier::Namespace(x)) => x.local.clone(),
+            _ => unreachable!("is_valid_wildcard_import only accepts supported specifiers"),
         };
         let mut results = vec![];
-        let expanded = self.expand_wildcard(decl);
 
-        for import in &expanded {
-            results.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
-                span: DUMMY_SP,
-                specifiers: vec![ImportSpecifier::Default(ImportDefaultSpecifier {
-                    span: DUMMY_SP,
-                    local: import.ident_import.clone(),
-                })],
-                src: Box::new(Str {
-                    span: DUMMY_SP,
-                    raw: None,
-                    value: import.import_src.clone().into(),
-                }),
-                type_only: false,
-                asserts: None,
-            })));
+        if self.mode == ImportMode::Eager {
+            for import in &expanded {
+                let item = match self.module {
+                    ModuleFormat::Esm => ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                        span: DUMMY_SP,
+                        specifiers: vec![ImportSpecifier::Default(ImportDefaultSpecifier {
+                            span: DUMMY_SP,
+                            local: import.ident_import.clone(),
+                        })],
+                        src: Box::new(Str {
+                            span: DUMMY_SP,
+                            raw: None,
+                            value: import.import_src.clone().into(),
+                        }),
+                        type_only: false,
+                        asserts: None,
+                    })),
+                    ModuleFormat::Cjs => ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                        kind: VarDeclKind::Const,
+                        declare: false,
+                        decls: vec![VarDeclarator {
+                            span: DUMMY_SP,
+                            definite: false,
+                            name: Pat::Ident(BindingIdent {
+                                id: import.ident_import.clone(),
+                                type_ann: None,
+                            }),
+                            init: Some(Box::new(Expr::Call(
+                                self.make_require_call(&import.import_src),
+                            ))),
+                        }],
+                        span: DUMMY_SP,
+                    })))),
+                };
+                results.push(item);
+            }
+        }
+
+        let mut tree = vec![];
+        for i in &expanded {
+            let value = match self.mode {
+                ImportMode::Eager => Expr::Ident(i.ident_import.clone()),
+                ImportMode::Lazy => Self::make_lazy_import_value(&i.import_src),
+            };
+            self.insert_into_map_tree(&mut tree, i.key_path.clone(), value);
         }
 
         let url_map = ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
@@ -131,22 +588,7 @@ impl GlobImporter {
                     },
                     type_ann: None,
                 }),
-                init: Some(Box::new(Expr::Object(ObjectLit {
-                    span: DUMMY_SP,
-                    props: expanded
-                        .iter()
-                        .map(|i| {
-                            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                                key: PropName::Ident(Ident {
-                                    span: DUMMY_SP,
-                                    optional: false,
-                                    sym: i.ident_obj.clone().into(),
-                                }),
-                                value: Box::new(Expr::Ident(i.ident_import.clone())),
-                            })))
-                        })
-                        .collect(),
-                }))),
+                init: Some(Box::new(Expr::Object(self.map_tree_to_object_lit(tree)))),
             }],
             span: DUMMY_SP,
         }))));
@@ -157,7 +599,14 @@ impl GlobImporter {
 
     fn next_variable_id(&mut self) -> Ident {
         self.id_counter += 1;
-        Ident::new(format!("$_import_{}", self.id_counter).into(), DUMMY_SP)
+        Ident::new(
+            format!("$_import_{}", self.id_counter).into(),
+            self.marked_span(),
+        )
+    }
+
+    fn marked_span(&self) -> swc_core::common::Span {
+        DUMMY_SP.with_ctxt(SyntaxContext::empty().apply_mark(self.unresolved_mark))
     }
 }
 
@@ -168,7 +617,7 @@ impl Fold for GlobImporter {
             .iter()
             .flat_map(|item| match item {
                 ModuleItem::ModuleDecl(ModuleDecl::Import(decl))
-                    if Self::is_valid_wildcard_import(decl) =>
+                    if self.is_valid_wildcard_import(decl) =>
                 {
                     self.split_wildcard_import(decl)
                 }
@@ -180,16 +629,56 @@ impl Fold for GlobImporter {
     }
 }
 
-pub fn glob_importer(cwd: PathBuf, file_name: PathBuf) -> GlobImporter {
+pub fn glob_importer(
+    cwd: PathBuf,
+    file_name: PathBuf,
+    mode: ImportMode,
+    unresolved_mark: Mark,
+    exclude: Vec<String>,
+) -> GlobImporter {
+    glob_importer_with_resolver(
+        cwd,
+        file_name,
+        mode,
+        unresolved_mark,
+        exclude,
+        ModuleFormat::default(),
+        None,
+        DuplicateStrategy::default(),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn glob_importer_with_resolver(
+    cwd: PathBuf,
+    file_name: PathBuf,
+    mode: ImportMode,
+    unresolved_mark: Mark,
+    exclude: Vec<String>,
+    module: ModuleFormat,
+    resolver: Option<Arc<dyn Resolve>>,
+    on_duplicate: DuplicateStrategy,
+) -> GlobImporter {
     GlobImporter {
         cwd,
         file_name,
         id_counter: 0,
+        mode,
+        unresolved_mark,
+        exclude,
+        module,
+        resolver,
+        on_duplicate,
     }
 }
 
 #[plugin_transform]
 pub fn process_transform(program: Program, metadata: TransformPluginProgramMetadata) -> Program {
+    let config: Config = metadata
+        .get_transform_plugin_config()
+        .map(|rule| serde_json::from_str(&rule).expect("invalid config for glob-import"))
+        .unwrap_or_default();
+
     let file_name = metadata
         .get_context(&TransformPluginMetadataContextKind::Filename)
         .map(PathBuf::from)
@@ -198,30 +687,55 @@ pub fn process_transform(program: Program, metadata: TransformPluginProgramMetad
     // swc mounts the current working directory under the /cwd path
     let cwd = PathBuf::from_str("/cwd").unwrap();
 
-    let mut importer = glob_importer(cwd, file_name);
+    let mut importer = glob_importer_with_resolver(
+        cwd,
+        file_name,
+        config.mode,
+        metadata.unresolved_mark,
+        config.exclude,
+        config.module,
+        None,
+        config.on_duplicate,
+    );
     program.fold_with(&mut importer)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::fs;
     use std::path::PathBuf;
     use swc_core::common::{chain, Mark};
     use swc_core::ecma::transforms::base::resolver;
     use swc_core::ecma::transforms::testing::{test, test_fixture};
     use swc_core::testing::fixture;
 
-    use super::glob_importer;
+    use super::{glob_importer_with_resolver, Config};
 
     #[fixture("tests/fixture/**/input.js")]
     fn fixture(input: PathBuf) {
         let output = input.with_file_name("output.js");
         let cwd = input.parent().unwrap().to_path_buf();
+        let config: Config = fs::read_to_string(input.with_file_name("config.json"))
+            .ok()
+            .map(|json| serde_json::from_str(&json).expect("invalid fixture config.json"))
+            .unwrap_or_default();
+
         test_fixture(
             Default::default(),
             &|_| {
+                let unresolved_mark = Mark::new();
                 chain!(
-                    resolver(Mark::new(), Mark::new(), false),
-                    glob_importer(cwd.clone(), input.clone())
+                    resolver(unresolved_mark, Mark::new(), false),
+                    glob_importer_with_resolver(
+                        cwd.clone(),
+                        input.clone(),
+                        config.mode,
+                        unresolved_mark,
+                        config.exclude.clone(),
+                        config.module,
+                        None,
+                        config.on_duplicate,
+                    )
                 )
             },
             &input,